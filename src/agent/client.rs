@@ -0,0 +1,63 @@
+use std::{
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+use anyhow::{Context, Result, anyhow};
+use log::debug;
+use tokio::net::UnixStream;
+
+use crate::agent::protocol::{self, Request, Response};
+
+const SPAWN_WAIT_ATTEMPTS: u32 = 50;
+const SPAWN_WAIT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Sends `request` to the running agent, auto-spawning it first if its
+/// socket isn't present.
+pub async fn request(request: Request) -> Result<Response> {
+    let socket_path = protocol::socket_path();
+    if !socket_path.exists() {
+        spawn_agent()?;
+        wait_for_socket().await?;
+    }
+
+    let mut stream = UnixStream::connect(&socket_path)
+        .await
+        .context("Failed to connect to overdrip agent")?;
+
+    protocol::write_message(&mut stream, &request).await?;
+
+    loop {
+        match protocol::read_message(&mut stream).await? {
+            Response::AuthUrl(url) => println!("Open this URL to finish logging in: {url}"),
+            response => return Ok(response),
+        }
+    }
+}
+
+fn spawn_agent() -> Result<()> {
+    let exe = std::env::current_exe().context("Failed to determine current executable")?;
+    debug!("auto-spawning overdrip agent");
+
+    Command::new(exe)
+        .arg("run")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to spawn overdrip agent")?;
+
+    Ok(())
+}
+
+async fn wait_for_socket() -> Result<()> {
+    let socket_path = protocol::socket_path();
+    for _ in 0..SPAWN_WAIT_ATTEMPTS {
+        if socket_path.exists() {
+            return Ok(());
+        }
+        tokio::time::sleep(SPAWN_WAIT_INTERVAL).await;
+    }
+
+    Err(anyhow!("Timed out waiting for overdrip agent to start"))
+}
@@ -0,0 +1,190 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use log::{debug, info, warn};
+use tokio::{
+    net::{UnixListener, UnixStream},
+    sync::{Mutex, oneshot, watch},
+};
+
+use crate::{
+    auth::{Auth, Tokens, provider::DynProvider, token_store::DynTokenStore},
+    build_provider, build_token_store,
+    config::{Config, MonitorConfig},
+};
+
+use self::protocol::{Request, Response, StatusInfo};
+
+pub mod client;
+pub mod protocol;
+mod watcher;
+
+struct AgentState {
+    config: Config,
+    // `Arc`-wrapped so `do_login` can clone it out from behind the mutex and
+    // await the interactive OAuth flow without holding the lock for the
+    // whole time, freezing every other request and the monitor loop.
+    auth: Arc<Auth<DynTokenStore, DynProvider>>,
+    tokens: Option<Tokens>,
+    started_at: Instant,
+    last_poll_at: Option<Instant>,
+    monitor_rx: watch::Receiver<MonitorConfig>,
+}
+
+/// Runs the overdrip agent in the foreground: holds the current tokens in
+/// memory, polls `MonitorConfig` on an interval, and serves `Request`s over
+/// a Unix domain socket so short-lived CLI invocations don't each have to
+/// re-read the token store.
+pub async fn run(config: Config, config_path: PathBuf) -> Result<()> {
+    let socket_path = protocol::socket_path();
+    if let Some(parent) = socket_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create agent directory {}", parent.display()))?;
+    }
+    if socket_path.exists() {
+        fs::remove_file(&socket_path).with_context(|| {
+            format!("Failed to remove stale agent socket {}", socket_path.display())
+        })?;
+    }
+
+    let provider = build_provider(&config);
+    let token_store = build_token_store(&config, provider.name())?;
+    let auth = Arc::new(Auth::new(token_store, provider));
+    let tokens = auth.load_tokens()?;
+    let monitor_rx = watcher::watch(config_path, config.monitor)?;
+
+    let state = Arc::new(Mutex::new(AgentState {
+        config,
+        auth,
+        tokens,
+        started_at: Instant::now(),
+        last_poll_at: None,
+        monitor_rx,
+    }));
+
+    let listener = UnixListener::bind(&socket_path)
+        .with_context(|| format!("Failed to bind agent socket {}", socket_path.display()))?;
+    info!("overdrip agent listening on {}", socket_path.display());
+
+    tokio::spawn(monitor_loop(state.clone()));
+
+    loop {
+        let (stream, _) = listener
+            .accept()
+            .await
+            .context("Failed to accept agent connection")?;
+
+        let conn_state = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, conn_state).await {
+                warn!("agent connection error: {e:#}");
+            }
+        });
+    }
+}
+
+/// Polls on `monitor.interval`, refreshing the in-memory cached tokens if
+/// they've expired. `auth`/`tokens` are cloned out and the refresh runs
+/// without holding `state`'s lock, since it's a network round-trip to the
+/// provider's token endpoint that would otherwise freeze every concurrent
+/// IPC request for its duration (the same hazard `do_login` avoids).
+async fn monitor_loop(state: Arc<Mutex<AgentState>>) {
+    loop {
+        let monitor = *state.lock().await.monitor_rx.borrow_and_update();
+        tokio::time::sleep(Duration::from_secs(monitor.interval.max(1))).await;
+
+        let mut guard = state.lock().await;
+        let monitor = *guard.monitor_rx.borrow_and_update();
+        let auth = guard.auth.clone();
+        let tokens = guard.tokens.clone();
+        guard.last_poll_at = Some(Instant::now());
+        drop(guard);
+
+        if let Some(tokens) = tokens {
+            match auth.refresh_if_needed(tokens).await {
+                Ok(refreshed) => state.lock().await.tokens = Some(refreshed),
+                Err(e) => warn!("failed to refresh access token during poll: {e:#}"),
+            }
+        }
+
+        debug!("polled monitor (threshold={})", monitor.threshold);
+    }
+}
+
+async fn handle_connection(mut stream: UnixStream, state: Arc<Mutex<AgentState>>) -> Result<()> {
+    let request: Request = protocol::read_message(&mut stream).await?;
+    debug!("agent received request: {request:?}");
+
+    let response = dispatch(request, &mut stream, &state).await;
+    protocol::write_message(&mut stream, &response).await
+}
+
+async fn dispatch(request: Request, stream: &mut UnixStream, state: &Arc<Mutex<AgentState>>) -> Response {
+    match request {
+        Request::Login => match do_login(stream, state).await {
+            Ok(()) => Response::Ok,
+            Err(e) => Response::Error(format!("{e:#}")),
+        },
+        Request::Logout => match do_logout(state).await {
+            Ok(()) => Response::Ok,
+            Err(e) => Response::Error(format!("{e:#}")),
+        },
+        Request::ConfigShow => match config_show(state).await {
+            Ok(toml) => Response::ConfigToml(toml),
+            Err(e) => Response::Error(format!("{e:#}")),
+        },
+        Request::Status => Response::Status(status(state).await),
+    }
+}
+
+/// Runs the login flow without holding `state`'s lock across the wait for
+/// the user to complete the OAuth consent screen, so polling and other IPC
+/// requests keep working while this is in flight. Only the brief moments
+/// spent cloning `auth` out and writing the resulting tokens back take the
+/// lock.
+async fn do_login(stream: &mut UnixStream, state: &Arc<Mutex<AgentState>>) -> Result<()> {
+    let auth = state.lock().await.auth.clone();
+
+    let (auth_url_tx, auth_url_rx) = oneshot::channel();
+    let login_auth = auth.clone();
+    let login = tokio::spawn(async move { login_auth.login(auth_url_tx).await });
+
+    let auth_url = auth_url_rx
+        .await
+        .context("login task ended before reporting its auth URL")?;
+    protocol::write_message(stream, &Response::AuthUrl(auth_url)).await?;
+
+    login.await.context("login task panicked")??;
+
+    state.lock().await.tokens = auth.load_tokens()?;
+    Ok(())
+}
+
+async fn do_logout(state: &Arc<Mutex<AgentState>>) -> Result<()> {
+    let mut guard = state.lock().await;
+    guard.auth.logout()?;
+    guard.tokens = None;
+    Ok(())
+}
+
+async fn config_show(state: &Arc<Mutex<AgentState>>) -> Result<String> {
+    let mut guard = state.lock().await;
+    let mut config = guard.config.clone();
+    config.monitor = *guard.monitor_rx.borrow_and_update();
+
+    toml::to_string_pretty(&config).context("Failed to serialize config")
+}
+
+async fn status(state: &Arc<Mutex<AgentState>>) -> StatusInfo {
+    let guard = state.lock().await;
+    StatusInfo {
+        uptime_secs: guard.started_at.elapsed().as_secs(),
+        logged_in: guard.tokens.is_some(),
+        last_poll_secs_ago: guard.last_poll_at.map(|at| at.elapsed().as_secs()),
+    }
+}
@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::project_dir;
+
+/// Path to the agent's Unix domain socket.
+pub fn socket_path() -> PathBuf {
+    project_dir().join("agent.sock")
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Request {
+    Login,
+    Logout,
+    ConfigShow,
+    Status,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Response {
+    Ok,
+    /// Sent ahead of the final response to `Request::Login`, carrying the URL
+    /// the user needs to open to complete the OAuth flow, since the agent
+    /// (not the CLI process the user is looking at) is the one driving login.
+    AuthUrl(String),
+    ConfigToml(String),
+    Status(StatusInfo),
+    Error(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusInfo {
+    pub uptime_secs: u64,
+    pub logged_in: bool,
+    pub last_poll_secs_ago: Option<u64>,
+}
+
+/// Writes a length-prefixed, JSON-serialized message: a 4-byte big-endian
+/// length followed by the payload.
+pub async fn write_message<T, W>(writer: &mut W, message: &T) -> Result<()>
+where
+    T: Serialize,
+    W: AsyncWriteExt + Unpin,
+{
+    let payload = serde_json::to_vec(message).context("Failed to serialize IPC message")?;
+    let len = u32::try_from(payload.len()).context("IPC message too large")?;
+
+    writer.write_all(&len.to_be_bytes()).await?;
+    writer.write_all(&payload).await?;
+    writer.flush().await?;
+
+    Ok(())
+}
+
+/// Reads a length-prefixed, JSON-serialized message written by `write_message`.
+pub async fn read_message<T, R>(reader: &mut R) -> Result<T>
+where
+    T: for<'de> Deserialize<'de>,
+    R: AsyncReadExt + Unpin,
+{
+    let mut len_buf = [0u8; 4];
+    reader
+        .read_exact(&mut len_buf)
+        .await
+        .context("Failed to read IPC message length")?;
+    let len = u32::from_be_bytes(len_buf) as usize;
+
+    let mut payload = vec![0u8; len];
+    reader
+        .read_exact(&mut payload)
+        .await
+        .context("Failed to read IPC message body")?;
+
+    serde_json::from_slice(&payload).context("Failed to deserialize IPC message")
+}
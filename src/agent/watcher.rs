@@ -0,0 +1,90 @@
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::{mpsc, watch};
+
+use crate::config::{MonitorConfig, read_config};
+
+/// How long to wait after the first filesystem event before re-parsing; events
+/// that arrive during the wait are folded into the same reload, so a short
+/// burst of writes from an editor only triggers one reload.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Watches `config_path`'s parent directory (rather than the file itself) for
+/// changes and publishes the latest known-good `MonitorConfig` as edits land,
+/// so a running agent's monitor loop can pick up a new interval/threshold
+/// without restarting. Editors commonly save by writing a temp file and
+/// renaming it over the target, which replaces the original inode; watching
+/// the directory and matching events by file name survives that, whereas
+/// watching the file path directly would stop firing after the first such
+/// save. Reloads go through `read_config`, not `load_config`: an edit that
+/// fails to parse, or a debounce window that fires while the file is
+/// transiently missing mid-save, is logged and ignored rather than
+/// overwriting the user's config with a freshly-initialized default.
+pub fn watch(config_path: PathBuf, initial: MonitorConfig) -> Result<watch::Receiver<MonitorConfig>> {
+    let (tx, rx) = watch::channel(initial);
+    let (fs_tx, mut fs_rx) = mpsc::channel::<()>(16);
+
+    let watch_dir = config_path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let file_name: Option<OsString> = config_path.file_name().map(OsString::from);
+
+    let mut watcher: RecommendedWatcher =
+        notify::recommended_watcher(move |res: notify::Result<Event>| match res {
+            Ok(event)
+                if matches!(
+                    event.kind,
+                    EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_)
+                ) =>
+            {
+                let matches_target = match &file_name {
+                    Some(name) => event.paths.iter().any(|p| p.file_name() == Some(name.as_os_str())),
+                    None => true,
+                };
+                if matches_target && fs_tx.blocking_send(()).is_err() {
+                    debug!("config watcher channel closed; agent is shutting down");
+                }
+            }
+            Ok(_) => {}
+            Err(e) => warn!("config watcher error: {e}"),
+        })
+        .context("Failed to create config file watcher")?;
+
+    watcher
+        .watch(&watch_dir, RecursiveMode::NonRecursive)
+        .with_context(|| format!("Failed to watch config directory {}", watch_dir.display()))?;
+
+    tokio::spawn(async move {
+        // Keep the watcher alive for as long as this task is running.
+        let _watcher = watcher;
+
+        while fs_rx.recv().await.is_some() {
+            tokio::time::sleep(DEBOUNCE).await;
+            while fs_rx.try_recv().is_ok() {}
+
+            match read_config(&config_path) {
+                Ok(config) => {
+                    debug!("reloaded config from {}", config_path.display());
+                    let _ = tx.send(config.monitor);
+                }
+                Err(e) => {
+                    warn!(
+                        "ignoring invalid config edit at {}: {e:#}",
+                        config_path.display()
+                    );
+                }
+            }
+        }
+    });
+
+    Ok(rx)
+}
@@ -1,53 +1,142 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
 
+use crate::auth::provider::Provider;
 use crate::auth::token_store::TokenStore;
 
 pub mod oauth;
+pub mod provider;
 pub mod token_store;
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Seconds of safety margin applied before `expires_in` actually elapses.
+const EXPIRY_SKEW_SECS: u64 = 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Tokens {
     access_token: String,
     refresh_token: String,
     expires_in: u64,
+    obtained_at: u64,
+}
+
+impl Tokens {
+    fn is_expired(&self) -> bool {
+        now() >= self.obtained_at + self.expires_in.saturating_sub(EXPIRY_SKEW_SECS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens_obtained(obtained_at: u64, expires_in: u64) -> Tokens {
+        Tokens {
+            access_token: "access".to_string(),
+            refresh_token: "refresh".to_string(),
+            expires_in,
+            obtained_at,
+        }
+    }
+
+    #[test]
+    fn fresh_tokens_are_not_expired() {
+        let tokens = tokens_obtained(now(), 3600);
+        assert!(!tokens.is_expired());
+    }
+
+    #[test]
+    fn tokens_past_their_expiry_are_expired() {
+        let tokens = tokens_obtained(now() - 3700, 3600);
+        assert!(tokens.is_expired());
+    }
+
+    #[test]
+    fn tokens_within_the_skew_window_are_already_expired() {
+        // expires_in is 30s away from elapsing, inside EXPIRY_SKEW_SECS
+        // (60s), so this should be treated as expired ahead of time.
+        let tokens = tokens_obtained(now() - (3600 - 30), 3600);
+        assert!(tokens.is_expired());
+    }
 }
 
-pub struct Auth<S>
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock is before unix epoch")
+        .as_secs()
+}
+
+pub struct Auth<S, P>
 where
     S: TokenStore,
+    P: Provider,
 {
     token_store: S,
+    provider: P,
 }
 
-impl<S> Auth<S>
+impl<S, P> Auth<S, P>
 where
     S: TokenStore,
+    P: Provider,
 {
-    pub fn new(token_store: S) -> Self {
-        Auth { token_store }
+    pub fn new(token_store: S, provider: P) -> Self {
+        Auth {
+            token_store,
+            provider,
+        }
     }
 
-    pub async fn login(&self) -> Result<()> {
+    /// Runs the OAuth login flow, reporting the URL the user needs to open
+    /// over `auth_url_tx` before blocking on the callback. The sender takes
+    /// the place of printing the URL directly, since the caller (the agent)
+    /// isn't necessarily the process the user is watching.
+    pub async fn login(&self, auth_url_tx: oneshot::Sender<String>) -> Result<()> {
         let pkce = oauth::generate_pkce_challenge();
-        let auth_url = format!(
-            "https://accounts.google.com/o/oauth2/v2/auth?\
-               client_id={}&\
-               redirect_uri=http://localhost:8080/callback&\
-               response_type=code&\
-               scope=openid%20email%20profile&\
-               code_challenge={}&\
-               code_challenge_method=S256",
-            oauth::CLIENT_ID,
-            pkce.challenge,
-        );
-
-        println!("Login at: {auth_url}",);
-        let code = oauth::start_oath_server().await?;
-        let tokens = oauth::exchange_code_for_tokens(&pkce.verifier, &code).await?;
+        let state = oauth::generate_state();
+        let auth_url = self.provider.build_authorize_url(&pkce, &state);
+
+        // Ignore a dropped receiver: the caller may have stopped listening,
+        // but the flow itself should still complete and persist its tokens.
+        let _ = auth_url_tx.send(auth_url);
+
+        let code = oauth::start_oath_server(&state).await?;
+        let tokens = oauth::exchange_code_for_tokens(&self.provider, &pkce.verifier, &code).await?;
 
         self.token_store
             .save_tokens(&tokens)
             .context("Failed to save tokens")
     }
+
+    pub fn logout(&self) -> Result<()> {
+        self.token_store.clear_tokens()
+    }
+
+    /// Loads whatever tokens are currently persisted, without checking expiry.
+    pub fn load_tokens(&self) -> Result<Option<Tokens>> {
+        self.token_store.load_tokens().context("Failed to load tokens")
+    }
+
+    /// Returns `tokens` as-is if still valid, otherwise refreshes them and
+    /// persists the result. Lets a long-lived caller (e.g. the agent) hold
+    /// tokens in memory and only touch the token store when a refresh
+    /// actually happens.
+    pub async fn refresh_if_needed(&self, tokens: Tokens) -> Result<Tokens> {
+        if !tokens.is_expired() {
+            return Ok(tokens);
+        }
+
+        let refreshed = oauth::refresh_tokens(&self.provider, &tokens.refresh_token)
+            .await
+            .context("Failed to refresh access token")?;
+
+        self.token_store
+            .save_tokens(&refreshed)
+            .context("Failed to save refreshed tokens")?;
+
+        Ok(refreshed)
+    }
 }
@@ -1,4 +1,4 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
 use axum::{Router, extract::Query, response::Html, routing::get};
 use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
 use log::{debug, warn};
@@ -10,12 +10,36 @@ use tokio::{
     sync::{mpsc::channel, oneshot},
 };
 
-pub(crate) const CLIENT_ID: &str = env!("OAUTH_CLIENT_ID");
-pub(crate) const CLIENT_SECRET: &str = env!("OAUTH_CLIENT_SECRET");
+use crate::auth::provider::Provider;
+use crate::auth::{Tokens, now};
+
+pub(crate) const GOOGLE_CLIENT_ID: &str = env!("OAUTH_CLIENT_ID");
+pub(crate) const GOOGLE_CLIENT_SECRET: &str = env!("OAUTH_CLIENT_SECRET");
 
 #[derive(Deserialize, Debug)]
 struct AuthCallback {
     code: String,
+    state: String,
+}
+
+/// Outcome of the local OAuth callback, distinguishing a valid authorization
+/// code from a `state` mismatch so the caller can tell CSRF rejection apart
+/// from a plain timeout.
+#[derive(Debug, PartialEq)]
+enum CallbackOutcome {
+    Code(String),
+    StateMismatch,
+}
+
+/// Compares the callback's `state` against the one generated for this login,
+/// rejecting a mismatch as a possible CSRF attempt rather than accepting the code.
+fn callback_outcome(expected_state: &str, callback: AuthCallback) -> CallbackOutcome {
+    if callback.state != expected_state {
+        warn!("OAuth callback state did not match; rejecting as a possible CSRF attempt");
+        return CallbackOutcome::StateMismatch;
+    }
+
+    CallbackOutcome::Code(callback.code)
 }
 
 pub(crate) struct PkceChallenge {
@@ -39,18 +63,36 @@ pub(crate) fn generate_pkce_challenge() -> PkceChallenge {
     }
 }
 
-pub(crate) async fn start_oath_server() -> Result<String> {
-    let (tx, mut rx) = channel::<String>(1);
+/// Generates a cryptographically random CSRF `state` value to tie an
+/// authorization request to its callback.
+pub(crate) fn generate_state() -> String {
+    let random_bytes: [u8; 32] = rand::rng().random();
+    URL_SAFE_NO_PAD.encode(random_bytes)
+}
+
+pub(crate) async fn start_oath_server(expected_state: &str) -> Result<String> {
+    let (tx, mut rx) = channel::<CallbackOutcome>(1);
     let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let expected_state = expected_state.to_string();
 
     let app = Router::new().route(
         "/callback",
         get(move |Query(params): Query<AuthCallback>| async move {
-            if let Err(e) = tx.send(params.code).await {
-                warn!("Failed to send auth code to receiver: {}", e);
-                Html("<h1>Authentication failed</h1><p>The authentication session may have timed out. Please try again.</p>")
-            } else {
-                Html("<h1>Authentication successful!</h1><p>You can now close this window.</p>")
+            match callback_outcome(&expected_state, params) {
+                CallbackOutcome::StateMismatch => {
+                    if let Err(e) = tx.send(CallbackOutcome::StateMismatch).await {
+                        warn!("Failed to send state mismatch to receiver: {}", e);
+                    }
+                    Html("<h1>Authentication failed</h1><p>Invalid state parameter. Please restart the login.</p>")
+                }
+                CallbackOutcome::Code(code) => {
+                    if let Err(e) = tx.send(CallbackOutcome::Code(code)).await {
+                        warn!("Failed to send auth code to receiver: {}", e);
+                        Html("<h1>Authentication failed</h1><p>The authentication session may have timed out. Please try again.</p>")
+                    } else {
+                        Html("<h1>Authentication successful!</h1><p>You can now close this window.</p>")
+                    }
+                }
             }
         }),
     );
@@ -66,7 +108,7 @@ pub(crate) async fn start_oath_server() -> Result<String> {
         }
     });
 
-    let code = rx
+    let outcome = rx
         .recv()
         .await
         .context("Channel closed before receiving auth code")?;
@@ -79,7 +121,12 @@ pub(crate) async fn start_oath_server() -> Result<String> {
     // Wait for server to finish
     server_handle.await.context("Server task panicked")?;
 
-    Ok(code)
+    match outcome {
+        CallbackOutcome::Code(code) => Ok(code),
+        CallbackOutcome::StateMismatch => {
+            Err(anyhow!("OAuth callback state mismatch; possible CSRF attempt"))
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -93,27 +140,131 @@ struct TokenRequest {
 }
 
 impl TokenRequest {
-    fn new(code: &str, code_verifier: &str) -> Self {
+    fn new(provider: &impl Provider, code: &str, code_verifier: &str) -> Self {
         Self {
             code: code.to_string(),
-            client_id: CLIENT_ID.to_string(),
-            client_secret: CLIENT_SECRET.to_string(),
+            client_id: provider.client_id().to_string(),
+            client_secret: provider.client_secret().to_string(),
             code_verifier: code_verifier.to_string(),
-            redirect_uri: "http://localhost:8080/callback".to_string(),
+            redirect_uri: provider.redirect_uri().to_string(),
             grant_type: "authorization_code".to_string(),
         }
     }
 }
 
-pub(crate) async fn exchange_code_for_tokens(code_verifier: &str, code: &str) -> Result<()> {
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: String,
+    expires_in: u64,
+}
+
+pub(crate) async fn exchange_code_for_tokens(
+    provider: &impl Provider,
+    code_verifier: &str,
+    code: &str,
+) -> Result<Tokens> {
     let client = reqwest::Client::new();
     let request = client
-        .post("https://oauth2.googleapis.com/token")
-        .form(&TokenRequest::new(code, code_verifier));
+        .post(provider.token_url())
+        .form(&TokenRequest::new(provider, code, code_verifier));
     debug!("requesting tokens: {request:?}");
 
-    let res = request.send().await?;
-    debug!("Token response: {:?}", res.text().await?);
+    let response: TokenResponse = request
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+        .context("Failed to parse token response")?;
+
+    Ok(Tokens {
+        access_token: response.access_token,
+        refresh_token: response.refresh_token,
+        expires_in: response.expires_in,
+        obtained_at: now(),
+    })
+}
+
+#[derive(Debug, Serialize)]
+struct RefreshRequest {
+    grant_type: String,
+    refresh_token: String,
+    client_id: String,
+    client_secret: String,
+}
+
+impl RefreshRequest {
+    fn new(provider: &impl Provider, refresh_token: &str) -> Self {
+        Self {
+            grant_type: "refresh_token".to_string(),
+            refresh_token: refresh_token.to_string(),
+            client_id: provider.client_id().to_string(),
+            client_secret: provider.client_secret().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RefreshResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    expires_in: u64,
+}
+
+pub(crate) async fn refresh_tokens(provider: &impl Provider, refresh_token: &str) -> Result<Tokens> {
+    let client = reqwest::Client::new();
+    let request = client
+        .post(provider.token_url())
+        .form(&RefreshRequest::new(provider, refresh_token));
+    debug!("refreshing tokens: {request:?}");
 
-    Ok(())
+    let response: RefreshResponse = request
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+        .context("Failed to parse token refresh response")?;
+
+    Ok(Tokens {
+        access_token: response.access_token,
+        refresh_token: response
+            .refresh_token
+            .unwrap_or_else(|| refresh_token.to_string()),
+        expires_in: response.expires_in,
+        obtained_at: now(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching_state_yields_the_code() {
+        let callback = AuthCallback {
+            code: "abc123".to_string(),
+            state: "expected".to_string(),
+        };
+
+        assert_eq!(
+            callback_outcome("expected", callback),
+            CallbackOutcome::Code("abc123".to_string())
+        );
+    }
+
+    #[test]
+    fn mismatched_state_is_rejected() {
+        let callback = AuthCallback {
+            code: "abc123".to_string(),
+            state: "attacker-controlled".to_string(),
+        };
+
+        assert_eq!(
+            callback_outcome("expected", callback),
+            CallbackOutcome::StateMismatch
+        );
+    }
 }
@@ -0,0 +1,195 @@
+use crate::auth::oauth::PkceChallenge;
+use crate::config::OidcConfig;
+
+/// Identifies an OAuth 2.0 identity provider: the endpoints and credentials
+/// needed to drive the authorization-code + PKCE flow against it, and the
+/// namespace used to keep its tokens separate from other providers.
+pub trait Provider {
+    /// Short, filesystem-safe name used to namespace stored tokens (e.g. "google").
+    fn name(&self) -> &str;
+
+    fn auth_base_url(&self) -> &str;
+
+    fn token_url(&self) -> &str;
+
+    fn scopes(&self) -> &[String];
+
+    fn client_id(&self) -> &str;
+
+    fn client_secret(&self) -> &str;
+
+    fn redirect_uri(&self) -> &str {
+        "http://localhost:8080/callback"
+    }
+
+    fn build_authorize_url(&self, pkce: &PkceChallenge, state: &str) -> String {
+        format!(
+            "{}?\
+               client_id={}&\
+               redirect_uri={}&\
+               response_type=code&\
+               scope={}&\
+               state={}&\
+               code_challenge={}&\
+               code_challenge_method=S256",
+            self.auth_base_url(),
+            self.client_id(),
+            self.redirect_uri(),
+            self.scopes().join("%20"),
+            state,
+            pkce.challenge,
+        )
+    }
+}
+
+pub struct Google {
+    scopes: Vec<String>,
+}
+
+impl Google {
+    pub fn new() -> Self {
+        Self {
+            scopes: vec!["openid".to_string(), "email".to_string(), "profile".to_string()],
+        }
+    }
+}
+
+impl Default for Google {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Provider for Google {
+    fn name(&self) -> &str {
+        "google"
+    }
+
+    fn auth_base_url(&self) -> &str {
+        "https://accounts.google.com/o/oauth2/v2/auth"
+    }
+
+    fn token_url(&self) -> &str {
+        "https://oauth2.googleapis.com/token"
+    }
+
+    fn scopes(&self) -> &[String] {
+        &self.scopes
+    }
+
+    fn client_id(&self) -> &str {
+        super::oauth::GOOGLE_CLIENT_ID
+    }
+
+    fn client_secret(&self) -> &str {
+        super::oauth::GOOGLE_CLIENT_SECRET
+    }
+}
+
+/// A generic OpenID Connect provider, fully configured from `Config`, for
+/// identities beyond Google (e.g. a self-hosted Keycloak or Authentik instance).
+pub struct Oidc {
+    name: String,
+    auth_base_url: String,
+    token_url: String,
+    client_id: String,
+    client_secret: String,
+    scopes: Vec<String>,
+}
+
+impl Oidc {
+    pub fn new(config: &OidcConfig) -> Self {
+        Self {
+            name: config.name.clone(),
+            auth_base_url: config.auth_base_url.clone(),
+            token_url: config.token_url.clone(),
+            client_id: config.client_id.clone(),
+            client_secret: config.client_secret.clone(),
+            scopes: config.scopes.clone(),
+        }
+    }
+}
+
+impl Provider for Oidc {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn auth_base_url(&self) -> &str {
+        &self.auth_base_url
+    }
+
+    fn token_url(&self) -> &str {
+        &self.token_url
+    }
+
+    fn scopes(&self) -> &[String] {
+        &self.scopes
+    }
+
+    fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    fn client_secret(&self) -> &str {
+        &self.client_secret
+    }
+}
+
+impl<T: Provider + ?Sized> Provider for Box<T> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn auth_base_url(&self) -> &str {
+        (**self).auth_base_url()
+    }
+
+    fn token_url(&self) -> &str {
+        (**self).token_url()
+    }
+
+    fn scopes(&self) -> &[String] {
+        (**self).scopes()
+    }
+
+    fn client_id(&self) -> &str {
+        (**self).client_id()
+    }
+
+    fn client_secret(&self) -> &str {
+        (**self).client_secret()
+    }
+
+    fn redirect_uri(&self) -> &str {
+        (**self).redirect_uri()
+    }
+}
+
+/// A `Provider` chosen at runtime based on `Config`, used where a single
+/// concrete provider type can't be named (e.g. the agent, which picks one
+/// of several implementations depending on what's configured).
+pub type DynProvider = Box<dyn Provider + Send + Sync>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::oauth::GOOGLE_CLIENT_ID;
+
+    #[test]
+    fn google_authorize_url_includes_scopes_state_and_pkce_challenge() {
+        let google = Google::new();
+        let pkce = PkceChallenge {
+            verifier: "verifier".to_string(),
+            challenge: "challenge-value".to_string(),
+        };
+
+        let url = google.build_authorize_url(&pkce, "csrf-state");
+
+        let expected = format!(
+            "https://accounts.google.com/o/oauth2/v2/auth?client_id={GOOGLE_CLIENT_ID}&redirect_uri=http://localhost:8080/callback&response_type=code&scope=openid%20email%20profile&state=csrf-state&code_challenge=challenge-value&code_challenge_method=S256"
+        );
+
+        assert_eq!(url, expected);
+    }
+}
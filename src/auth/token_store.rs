@@ -6,6 +6,7 @@ use std::{
 };
 
 use anyhow::{Context, Result};
+use keyring::Entry;
 
 use crate::auth::Tokens;
 
@@ -83,3 +84,64 @@ impl TokenStore for FileTokenStore {
         Ok(Some(tokens))
     }
 }
+
+/// Stores tokens in the platform secret service (Secret Service/libsecret on
+/// Linux, Keychain on macOS, Credential Manager on Windows) instead of a
+/// plaintext file.
+pub struct KeyringTokenStore {
+    entry: Entry,
+}
+
+impl KeyringTokenStore {
+    pub fn new(service: &str, user: &str) -> Result<Self> {
+        let entry = Entry::new(service, user).context("Failed to open keyring entry")?;
+        Ok(Self { entry })
+    }
+}
+
+impl TokenStore for KeyringTokenStore {
+    fn save_tokens(&self, tokens: &Tokens) -> Result<()> {
+        let data = serde_json::to_string(tokens).context("Failed to serialize tokens")?;
+        self.entry
+            .set_password(&data)
+            .context("Failed to save tokens to keyring")
+    }
+
+    fn load_tokens(&self) -> Result<Option<Tokens>> {
+        match self.entry.get_password() {
+            Ok(data) => {
+                let tokens = serde_json::from_str(&data)
+                    .context("Failed to parse tokens from keyring")?;
+                Ok(Some(tokens))
+            }
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e).context("Failed to load tokens from keyring"),
+        }
+    }
+
+    fn clear_tokens(&self) -> Result<()> {
+        match self.entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e).context("Failed to clear tokens from keyring"),
+        }
+    }
+}
+
+impl<T: TokenStore + ?Sized> TokenStore for Box<T> {
+    fn save_tokens(&self, tokens: &Tokens) -> Result<()> {
+        (**self).save_tokens(tokens)
+    }
+
+    fn load_tokens(&self) -> Result<Option<Tokens>> {
+        (**self).load_tokens()
+    }
+
+    fn clear_tokens(&self) -> Result<()> {
+        (**self).clear_tokens()
+    }
+}
+
+/// A `TokenStore` chosen at runtime based on `Config`, used where a single
+/// concrete backend type can't be named (e.g. the agent, which picks one of
+/// several implementations depending on what's configured).
+pub type DynTokenStore = Box<dyn TokenStore + Send + Sync>;
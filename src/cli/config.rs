@@ -3,8 +3,6 @@ use std::{env, path::Path, process::Command};
 use anyhow::{Context, Result};
 use log::warn;
 
-use crate::config::Config;
-
 #[derive(clap::Subcommand, Debug)]
 pub enum Subcommand {
     /// Edit the configuration file
@@ -30,7 +28,7 @@ pub fn edit(config_path: &Path) -> Result<()> {
     Ok(())
 }
 
-pub fn show(config: &Config) -> Result<()> {
-    println!("{}", toml::to_string_pretty(config)?);
+pub fn show(config_toml: &str) -> Result<()> {
+    println!("{config_toml}");
     Ok(())
 }
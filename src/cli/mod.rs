@@ -1,3 +1,5 @@
+pub mod config;
+
 /// Command line interface for Overdrip
 #[derive(clap::Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -13,15 +15,21 @@ pub struct Cli {
 
 #[derive(clap::Subcommand, Debug)]
 pub enum Subcommand {
-    /// Run the Overdrip service
+    /// Run the Overdrip agent in the foreground
     Run,
 
     /// Manage configuration
-    Config,
+    Config {
+        #[command(subcommand)]
+        subcommand: config::Subcommand,
+    },
 
     /// Authenticate with the service
     Login,
 
     /// Logout from the service
     Logout,
+
+    /// Show the status of the running agent
+    Status,
 }
@@ -4,7 +4,7 @@ use anyhow::{Context, Result};
 use log::{debug, info};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct MonitorConfig {
     pub interval: u64,
     pub threshold: f64,
@@ -19,9 +19,54 @@ impl Default for MonitorConfig {
     }
 }
 
-#[derive(Default, Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProviderConfig {
+    Google,
+    Oidc(OidcConfig),
+}
+
+impl Default for ProviderConfig {
+    fn default() -> Self {
+        ProviderConfig::Google
+    }
+}
+
+/// Configuration for a generic OpenID Connect provider, used when `provider.type`
+/// is `oidc` instead of one of the built-in providers.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OidcConfig {
+    pub name: String,
+    pub auth_base_url: String,
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub scopes: Vec<String>,
+}
+
+/// Where `Auth` persists OAuth tokens.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenStoreBackend {
+    /// A `0600` JSON file under the project data directory. Works anywhere,
+    /// including headless servers without a secret service.
+    #[default]
+    File,
+
+    /// The platform secret service (Secret Service/libsecret, Keychain,
+    /// Credential Manager).
+    Keyring,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub monitor: MonitorConfig,
+
+    #[serde(default)]
+    pub provider: ProviderConfig,
+
+    #[serde(default)]
+    pub token_store: TokenStoreBackend,
 }
 
 fn write_config(path: &Path, config: &Config) -> Result<()> {
@@ -49,6 +94,17 @@ fn init_config(path: &Path) -> Result<()> {
     write_config(path, &Config::default())
 }
 
+/// Reads and parses the config at `path` with no side effects — unlike
+/// `load_config`, a missing or invalid file is just an error rather than
+/// being replaced with a fresh default. For callers (like the config
+/// watcher) that must never write to `path` themselves.
+pub fn read_config(path: &Path) -> Result<Config> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file '{}'", path.display()))?;
+
+    Ok(toml::from_str(&contents)?)
+}
+
 pub fn load_config(path: &Path) -> Result<Config> {
     if !path.exists() {
         info!(
@@ -58,8 +114,5 @@ pub fn load_config(path: &Path) -> Result<Config> {
         init_config(path)?;
     }
 
-    let contents = fs::read_to_string(path)
-        .with_context(|| format!("Failed to read config file '{}'", path.display()))?;
-
-    Ok(toml::from_str(&contents)?)
+    read_config(path)
 }
@@ -1,17 +1,29 @@
 use std::path::PathBuf;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use directories::ProjectDirs;
 use log::warn;
 
-use crate::{auth::token_store::FileTokenStore, config::Config};
+use crate::{
+    agent::protocol::{Request, Response, StatusInfo},
+    auth::{
+        provider::{DynProvider, Google, Oidc},
+        token_store::{DynTokenStore, FileTokenStore, KeyringTokenStore},
+    },
+    config::{Config, ProviderConfig, TokenStoreBackend},
+};
 
+mod agent;
 mod auth;
 pub mod cli;
 pub mod config;
 
+const PROJECT_QUALIFIER: &str = "dev";
+const PROJECT_ORGANIZATION: &str = "sleb";
+const PROJECT_APPLICATION: &str = "overdrip";
+
 fn project_dir() -> PathBuf {
-    ProjectDirs::from("dev", "sleb", "overdrip")
+    ProjectDirs::from(PROJECT_QUALIFIER, PROJECT_ORGANIZATION, PROJECT_APPLICATION)
         .map(|d| d.data_dir().to_path_buf())
         .unwrap_or_else(|| {
             warn!("unable to determine user project dir, defaulting to current working dir");
@@ -19,36 +31,92 @@ fn project_dir() -> PathBuf {
         })
 }
 
+/// Service name under which `KeyringTokenStore` namespaces its secrets.
+fn keyring_service() -> String {
+    format!("{PROJECT_QUALIFIER}.{PROJECT_ORGANIZATION}.{PROJECT_APPLICATION}")
+}
+
 pub fn default_config_path() -> PathBuf {
     project_dir().join("config.toml")
 }
 
-pub fn default_auth_path() -> PathBuf {
-    project_dir().join("auth.json")
+/// Path to the token file for a given provider, e.g. `auth-google.json`, so
+/// multiple provider identities can coexist without clobbering each other.
+pub fn default_auth_path(provider_name: &str) -> PathBuf {
+    project_dir().join(format!("auth-{provider_name}.json"))
+}
+
+/// Builds the `Provider` selected by `config.provider`.
+fn build_provider(config: &Config) -> DynProvider {
+    match &config.provider {
+        ProviderConfig::Google => Box::new(Google::new()),
+        ProviderConfig::Oidc(oidc_config) => Box::new(Oidc::new(oidc_config)),
+    }
+}
+
+/// Builds the `TokenStore` selected by `config.token_store`, namespaced for `provider_name`.
+fn build_token_store(config: &Config, provider_name: &str) -> Result<DynTokenStore> {
+    match config.token_store {
+        TokenStoreBackend::File => Ok(Box::new(FileTokenStore::new(default_auth_path(
+            provider_name,
+        )))),
+        TokenStoreBackend::Keyring => Ok(Box::new(KeyringTokenStore::new(
+            &keyring_service(),
+            provider_name,
+        )?)),
+    }
 }
 
 #[derive(Debug)]
 pub struct Overdrip {
     pub config: Config,
+    config_path: PathBuf,
 }
 
 impl Overdrip {
-    pub fn new(config: Config) -> Self {
-        Overdrip { config }
+    pub fn new(config: Config, config_path: PathBuf) -> Self {
+        Overdrip {
+            config,
+            config_path,
+        }
     }
 
-    pub fn run(&self) -> Result<()> {
-        println!("Overdrip is running!");
-        Ok(())
+    /// Runs the overdrip agent in the foreground. This is what `overdrip run`
+    /// invokes directly, and what the CLI auto-spawns in the background when
+    /// no agent is listening yet.
+    pub async fn run(&self) -> Result<()> {
+        agent::run(self.config.clone(), self.config_path.clone()).await
     }
 
     pub async fn login(&self) -> Result<()> {
-        auth::Auth::new(FileTokenStore::new(self.config.tokens_path.clone()))
-            .login()
-            .await
+        match agent::client::request(Request::Login).await? {
+            Response::Ok => Ok(()),
+            Response::Error(message) => Err(anyhow!(message)),
+            other => Err(anyhow!("unexpected agent response: {other:?}")),
+        }
+    }
+
+    pub async fn logout(&self) -> Result<()> {
+        match agent::client::request(Request::Logout).await? {
+            Response::Ok => Ok(()),
+            Response::Error(message) => Err(anyhow!(message)),
+            other => Err(anyhow!("unexpected agent response: {other:?}")),
+        }
+    }
+
+    pub async fn config_show(&self) -> Result<String> {
+        match agent::client::request(Request::ConfigShow).await? {
+            Response::ConfigToml(toml) => Ok(toml),
+            Response::Error(message) => Err(anyhow!(message)),
+            other => Err(anyhow!("unexpected agent response: {other:?}")),
+        }
     }
 
-    pub fn logout(&self) -> Result<()> {
-        todo!()
+    pub async fn status(&self) -> Result<StatusInfo> {
+        match agent::client::request(Request::Status).await? {
+            Response::Status(status) => Ok(status),
+            Response::Error(message) => Err(anyhow!(message)),
+            other => Err(anyhow!("unexpected agent response: {other:?}")),
+        }
     }
 }
@@ -32,16 +32,17 @@ async fn run() -> Result<()> {
     debug!("config {config:?}");
     info!("config loaded successfully!");
 
-    let overdrip = Overdrip::new(config);
+    let overdrip = Overdrip::new(config, config_path.clone());
 
     match &cli.subcommand {
-        Subcommand::Run => overdrip.run()?,
+        Subcommand::Run => overdrip.run().await?,
         Subcommand::Config { subcommand } => match subcommand {
             config::Subcommand::Edit => config::edit(&config_path)?,
-            config::Subcommand::Show => config::show(&overdrip.config)?,
+            config::Subcommand::Show => config::show(&overdrip.config_show().await?)?,
         },
         Subcommand::Login => overdrip.login().await?,
-        Subcommand::Logout => overdrip.logout()?,
+        Subcommand::Logout => overdrip.logout().await?,
+        Subcommand::Status => println!("{:#?}", overdrip.status().await?),
     }
 
     Ok(())